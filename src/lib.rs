@@ -1,200 +1,632 @@
 use std::borrow::Borrow;
 use std::collections::hash_map::DefaultHasher;
-use std::fmt::Debug;
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasher, Hash};
 use std::mem;
 
-const INITIAL_SIZE: usize = 1;
+/// Smallest table the map ever allocates. A power of two so the ideal bucket
+/// is a cheap mask, and large enough that the 7/8 load factor always leaves a
+/// free slot for probes to terminate on.
+const MIN_BUCKETS: usize = 8;
 
+/// Default [`BuildHasher`] used by [`HashMap`] when none is supplied.
+///
+/// It hands out the standard library's [`DefaultHasher`], keeping the
+/// hashing behaviour identical to the original map while still going
+/// through the pluggable `BuildHasher` machinery.
+#[derive(Debug, Default, Clone)]
+pub struct DefaultHashBuilder;
+
+impl BuildHasher for DefaultHashBuilder {
+    type Hasher = DefaultHasher;
+    fn build_hasher(&self) -> DefaultHasher {
+        DefaultHasher::new()
+    }
+}
+
+/// An occupied slot. The cached `hash` lets Robin Hood recover a slot's
+/// probe-sequence length (distance from its ideal bucket) without rehashing,
+/// both while displacing on insert and while back-shifting on remove.
 #[derive(Debug)]
-pub enum Entry<K, V> {
-    Empty,
-    Del,
-    Pair { key: K, val: V },
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    val: V,
 }
-pub struct HashMap<K, V> {
-    table: Vec<Entry<K, V>>,
+
+pub struct HashMap<K, V, S = DefaultHashBuilder> {
+    table: Vec<Option<Bucket<K, V>>>,
     items: usize,
-    tombs: usize,
+    growth_left: usize,
+    hash_builder: S,
 }
 
-impl<K, V> HashMap<K, V> {
+impl<K, V> HashMap<K, V, DefaultHashBuilder> {
     pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder)
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Creates an empty map that will use `hash_builder` to hash keys.
+    pub fn with_hasher(hash_builder: S) -> Self {
         HashMap {
             table: Vec::new(),
             items: 0,
-            tombs: 0,
+            growth_left: 0,
+            hash_builder,
+        }
+    }
+    /// Creates an empty map with room for at least `capacity` elements,
+    /// using `hash_builder` to hash keys.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        // Match std: a zero request allocates nothing and stays lazy.
+        if capacity == 0 {
+            return Self::with_hasher(hash_builder);
+        }
+        let buckets = match buckets_for(capacity) {
+            Ok(buckets) => buckets,
+            Err(_) => panic!("capacity overflow"),
+        };
+        let mut table: Vec<Option<Bucket<K, V>>> = Vec::with_capacity(buckets);
+        table.extend((0..buckets).map(|_| None));
+        HashMap {
+            table,
+            items: 0,
+            growth_left: capacity_for(buckets),
+            hash_builder,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.items
+    }
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+    /// An iterator over the map's entries in arbitrary order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.table.iter(),
+        }
+    }
+    /// An iterator yielding mutable references to the map's values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.table.iter_mut(),
+        }
+    }
+    /// An iterator over the map's keys in arbitrary order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+    /// An iterator over the map's values in arbitrary order.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+    /// An iterator over mutable references to the map's values.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
         }
     }
 }
 
-impl<K, V> Default for HashMap<K, V> {
+impl<K, V, S: Default> Default for HashMap<K, V, S> {
     fn default() -> Self {
-        Self::new()
+        Self::with_hasher(S::default())
     }
 }
 
-impl<K, V> HashMap<K, V>
+/// Number of elements a table of `buckets` slots holds before it must grow,
+/// using a 7/8 load factor. `buckets` is always a power of two that is at
+/// least [`MIN_BUCKETS`], so at least one slot always stays free and probes
+/// are guaranteed to terminate.
+fn capacity_for(buckets: usize) -> usize {
+    buckets / 8 * 7
+}
+
+/// Smallest power-of-two bucket count (at least [`MIN_BUCKETS`]) whose
+/// [`capacity_for`] can hold `needed` elements under the load factor.
+fn buckets_for(needed: usize) -> Result<usize, TryReserveError> {
+    if needed == 0 {
+        return Ok(MIN_BUCKETS);
+    }
+    // capacity_for(b) = b / 8 * 7 >= needed  <=>  b >= ceil(needed * 8 / 7)
+    let scaled = needed
+        .checked_mul(8)
+        .ok_or(TryReserveError::CapacityOverflow)?;
+    let min_buckets = scaled.div_ceil(7);
+    let mut buckets = MIN_BUCKETS;
+    while buckets < min_buckets {
+        buckets = buckets
+            .checked_mul(2)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+    }
+    Ok(buckets)
+}
+
+/// The error returned by [`HashMap::try_reserve`] when the table cannot be
+/// grown to the requested size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// The requested capacity exceeds what can be represented.
+    CapacityOverflow,
+    /// The allocator failed to provide the requested bytes.
+    AllocError {
+        /// Size, in bytes, of the allocation that failed.
+        layout_size: usize,
+    },
+}
+
+impl<K, V, S> HashMap<K, V, S>
 where
-    K: Hash + Eq + Debug + Default,
-    V: Default + Debug,
+    K: Hash + Eq,
+    S: BuildHasher,
 {
     fn prehash<Q>(&self, key: &Q) -> u64
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        hasher.finish()
-    }
-    fn resize(&mut self, rehash_only: bool) {
-        let mut new_size = match self.table.len() {
-            0 => INITIAL_SIZE,
-            n => 2 * n,
-        };
-        if rehash_only {
-            new_size = self.table.len();
+        self.hash_builder.hash_one(key)
+    }
+    /// Resolves the bucket holding `key`.
+    ///
+    /// The Robin Hood invariant — no element sits further from its ideal
+    /// bucket than any element it passed — lets the scan give up as soon as
+    /// the query's probe distance exceeds the distance of the slot it is
+    /// looking at: a matching element would have displaced that slot.
+    fn find_index<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find_index_with_hash(self.prehash(key), key)
+    }
+    /// Like [`find_index`](Self::find_index) but takes an already computed
+    /// `hash`, letting callers that have hashed the key (e.g. [`entry`]) skip
+    /// a redundant `prehash`.
+    ///
+    /// [`entry`]: Self::entry
+    fn find_index_with_hash<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.table.is_empty() {
+            return None;
         }
-        let mut new_table: Vec<Entry<K, V>> = Vec::with_capacity(new_size);
-        new_table.extend((0..new_size).map(|_| Entry::Empty));
-        for entry in self.table.drain(..) {
-            if let Entry::Pair { mut key, mut val } = entry {
-                // rehash and add to new table
-                let mut hasher = DefaultHasher::new();
-                key.hash(&mut hasher);
-                let hash = hasher.finish();
-                let mut idx = (hash % new_size as u64) as usize;
-                while let Entry::Pair { key: _, val: _ } = new_table[idx] {
-                    idx = (idx + 1) % new_size
+        let mask = self.table.len() - 1;
+        let mut pos = (hash as usize) & mask;
+        let mut dist = 0;
+        loop {
+            match &self.table[pos] {
+                None => return None,
+                Some(b) => {
+                    let b_psl = pos.wrapping_sub((b.hash as usize) & mask) & mask;
+                    if dist > b_psl {
+                        return None;
+                    }
+                    if b.hash == hash && b.key.borrow() == key {
+                        return Some(pos);
+                    }
                 }
-                let mut nk: K = Default::default();
-                let mut nv: V = Default::default();
-                mem::swap(&mut nk, &mut key);
-                mem::swap(&mut nv, &mut val);
-                new_table[idx] = Entry::Pair { key: nk, val: nv };
             }
+            pos = (pos + 1) & mask;
+            dist += 1;
         }
-        self.table = new_table;
-        self.tombs = 0;
     }
-    pub fn len(&self) -> usize {
-        self.items
+    /// Places a `(key, value)` with precomputed `hash` following the Robin
+    /// Hood rule, displacing any "richer" element (one closer to its ideal
+    /// bucket) so probe distances stay balanced. Returns the final index of
+    /// the inserted key and the previous value if it replaced one.
+    fn robin_hood_insert(&mut self, hash: u64, key: K, value: V) -> (usize, Option<V>) {
+        let mask = self.table.len() - 1;
+        let mut pos = (hash as usize) & mask;
+        let mut dist = 0;
+        let mut carry = Bucket { hash, key, val: value };
+        let mut home = None;
+        loop {
+            match &mut self.table[pos] {
+                None => {
+                    self.table[pos] = Some(carry);
+                    self.items += 1;
+                    self.growth_left -= 1;
+                    return (home.unwrap_or(pos), None);
+                }
+                Some(existing) => {
+                    // Only the original key can already be present; once we
+                    // start carrying a displaced (unique) element there is
+                    // nothing left to match against.
+                    if home.is_none() && existing.hash == hash && existing.key == carry.key {
+                        let old = mem::replace(&mut existing.val, carry.val);
+                        return (pos, Some(old));
+                    }
+                    let existing_psl = pos.wrapping_sub((existing.hash as usize) & mask) & mask;
+                    if existing_psl < dist {
+                        mem::swap(existing, &mut carry);
+                        home.get_or_insert(pos);
+                        dist = existing_psl;
+                    }
+                }
+            }
+            pos = (pos + 1) & mask;
+            dist += 1;
+        }
     }
-    pub fn is_empty(&self) -> bool {
-        self.items == 0
+    /// Removes the element at `pos` and back-shifts the following run so no
+    /// tombstone is left behind: each subsequent element that is not already
+    /// in its ideal bucket slides back by one, until an empty slot or a
+    /// zero-distance element is reached.
+    fn erase(&mut self, pos: usize) -> V {
+        let removed = self.table[pos].take().unwrap();
+        self.items -= 1;
+        self.growth_left += 1;
+        let mask = self.table.len() - 1;
+        let mut prev = pos;
+        let mut next = (pos + 1) & mask;
+        loop {
+            let shift = match &self.table[next] {
+                Some(b) => (next.wrapping_sub((b.hash as usize) & mask) & mask) != 0,
+                None => false,
+            };
+            if !shift {
+                break;
+            }
+            self.table[prev] = self.table[next].take();
+            prev = next;
+            next = (next + 1) & mask;
+        }
+        removed.val
     }
-
-    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        if self.table.is_empty() || self.items >= 3 * self.table.len() / 4 {
-            self.resize(false);
+    /// Reserves room for at least `additional` more elements, growing the
+    /// table directly to a capacity that fits them. Panics on capacity
+    /// overflow or allocation failure; see [`try_reserve`](Self::try_reserve)
+    /// for the fallible form.
+    pub fn reserve(&mut self, additional: usize) {
+        match self.try_reserve(additional) {
+            Ok(()) => {}
+            Err(TryReserveError::CapacityOverflow) => panic!("capacity overflow"),
+            Err(TryReserveError::AllocError { .. }) => {
+                panic!("allocation failed while growing HashMap")
+            }
         }
-        let mut nv = value;
+    }
+    /// Reserves room for at least `additional` more elements, returning an
+    /// error instead of aborting if the table cannot be grown. This lets
+    /// latency-sensitive callers pre-size the table for a known number of
+    /// inserts and handle allocation failure themselves.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        if self.growth_left >= additional {
+            return Ok(());
+        }
+        let needed = self
+            .items
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        let mut new_buckets = buckets_for(needed)?;
+        if new_buckets < self.table.len() {
+            new_buckets = self.table.len();
+        }
+        self.try_resize(new_buckets)
+    }
+    /// Allocates a fresh table of `new_buckets` slots and re-inserts every
+    /// live element. Uses [`Vec::try_reserve`] so an allocator failure
+    /// surfaces as an error.
+    fn try_resize(&mut self, new_buckets: usize) -> Result<(), TryReserveError> {
+        let layout_size = new_buckets
+            .checked_mul(mem::size_of::<Option<Bucket<K, V>>>())
+            .filter(|&size| size <= isize::MAX as usize)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        let mut new_table: Vec<Option<Bucket<K, V>>> = Vec::new();
+        new_table
+            .try_reserve(new_buckets)
+            .map_err(|_| TryReserveError::AllocError { layout_size })?;
+        new_table.extend((0..new_buckets).map(|_| None));
+        let old = mem::replace(&mut self.table, new_table);
+        self.items = 0;
+        self.growth_left = capacity_for(new_buckets);
+        for b in old.into_iter().flatten() {
+            self.robin_hood_insert(b.hash, b.key, b.val);
+        }
+        Ok(())
+    }
+
+    /// Gets the entry for `key` so it can be inserted or modified in place.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        // Hash once and thread it through: the probe below and, on the vacant
+        // path, the eventual `robin_hood_insert` both reuse this `hash`.
         let hash = self.prehash(&key);
-        let mut idx = (hash % self.table.len() as u64) as usize;
-        let mut cnt = 0;
-        while !matches!(self.table[idx], Entry::Empty) {
-            // pair
-            if let Entry::Pair {
-                key: ekey,
-                val: eval,
-            } = &mut self.table[idx]
-            {
-                if key.borrow() == ekey {
-                    // existing key
-                    mem::swap(eval, &mut nv);
-                    return Some(nv);
-                }
-            }
-            idx = (idx + 1) % self.table.len();
-            if cnt > self.table.len() {
-                panic!("Infinite loop!")
-            }
-            cnt += 1;
+        if let Some(idx) = self.find_index_with_hash(hash, &key) {
+            Entry::Occupied(OccupiedEntry { map: self, idx })
+        } else {
+            // Only a genuine insertion needs room; an update reached through
+            // an occupied entry must not grow the table. `reserve(1)` may
+            // resize and move every element, so there is no stable slot index
+            // to cache — `VacantEntry::insert` re-walks the probe sequence
+            // (reusing `hash`) on the final table, which is also where the
+            // Robin Hood displacement has to happen anyway.
+            self.reserve(1);
+            Entry::Vacant(VacantEntry {
+                map: self,
+                key,
+                hash,
+            })
         }
-        // new key
-        self.table[idx] = Entry::Pair { key, val: nv };
-        self.items += 1;
-        None
     }
-    pub fn contains_key<Q: ?Sized>(&mut self, key: &Q) -> bool
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.reserve(1);
+        let hash = self.prehash(&key);
+        let (_, old) = self.robin_hood_insert(hash, key, value);
+        old
+    }
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
         K: Borrow<Q>,
-        Q: Hash + Eq + Debug,
+        Q: Hash + Eq + ?Sized,
     {
-        self.get(key).is_some()
+        self.find_index(key).is_some()
     }
-    pub fn get<Q: ?Sized>(&mut self, key: &Q) -> Option<&V>
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
         K: Borrow<Q>,
-        Q: Hash + Eq + Debug,
+        Q: Hash + Eq + ?Sized,
     {
-        if self.table.is_empty() {
-            return None;
+        let idx = self.find_index(key)?;
+        self.table[idx].as_ref().map(|b| &b.val)
+    }
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find_index(key)?;
+        Some(self.erase(idx))
+    }
+}
+
+/// A view into a single map slot, reached through [`HashMap::entry`].
+pub enum Entry<'a, K, V, S> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+/// A slot that is known to hold `key`.
+pub struct OccupiedEntry<'a, K, V, S> {
+    map: &'a mut HashMap<K, V, S>,
+    idx: usize,
+}
+
+/// An entry for a key that is not in the map, holding the owned `key` to
+/// place and its precomputed hash; no slot index is kept.
+pub struct VacantEntry<'a, K, V, S> {
+    map: &'a mut HashMap<K, V, S>,
+    key: K,
+    hash: u64,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S> {
+    /// Ensures a value is in the entry, inserting `default` if vacant.
+    pub fn or_insert(self, default: V) -> &'a mut V
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default),
         }
-        // rehash if contaminated
-        if self.items + self.tombs > 3 * self.table.len() / 4 {
-            self.resize(true);
+    }
+    /// Ensures a value is in the entry, inserting the result of `default` if
+    /// vacant. The closure is only called when no value is present.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default()),
         }
-        let hash = self.prehash(key);
-        let mut idx = (hash % self.table.len() as u64) as usize;
-        let mut cnt = 0;
-        while !matches!(self.table[idx], Entry::Empty) {
-            if let Entry::Pair { key: ek, val: ev } = &self.table[idx] {
-                if ek.borrow() == key {
-                    // found and return
-                    return Some(ev);
-                }
-            }
-            // linear probing
-            idx = (idx + 1) % self.table.len();
-            if cnt > self.table.len() {
-                panic!("Infinite loop!")
-            }
-            cnt += 1;
+    }
+    /// Runs `f` on the value if the entry is occupied, then returns the entry.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(ref mut e) = self {
+            f(e.get_mut());
         }
-        // not found
-        None
+        self
     }
-    pub fn remove<Q: ?Sized>(&mut self, key: &Q) -> Option<V>
+    /// Returns a reference to this entry's key.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    V: Default,
+{
+    /// Ensures a value is in the entry, inserting `V::default()` if vacant.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.map.table[self.idx].as_ref().unwrap().key
+    }
+    pub fn get(&self) -> &V {
+        &self.map.table[self.idx].as_ref().unwrap().val
+    }
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.table[self.idx].as_mut().unwrap().val
+    }
+    /// Converts the entry into a mutable reference with the map's lifetime.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.table[self.idx].as_mut().unwrap().val
+    }
+    /// Replaces the value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+    /// Removes the slot and returns its value.
+    pub fn remove(self) -> V
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + Debug,
+        K: Hash + Eq,
+        S: BuildHasher,
     {
-        if self.table.is_empty() {
-            return None;
-        }
-        // rehash if contaminated
-        if self.items + self.tombs > 3 * self.table.len() / 4 {
-            self.resize(true);
+        self.map.erase(self.idx)
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+    /// Takes ownership of the key back out of the vacant entry.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+    /// Places `value` into the map and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        let (idx, _) = self.map.robin_hood_insert(self.hash, self.key, value);
+        &mut self.map.table[idx].as_mut().unwrap().val
+    }
+}
+
+/// Iterator over `(&K, &V)` pairs, skipping empty slots.
+pub struct Iter<'a, K, V> {
+    inner: std::slice::Iter<'a, Option<Bucket<K, V>>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .by_ref()
+            .find_map(|slot| slot.as_ref().map(|b| (&b.key, &b.val)))
+    }
+}
+
+/// Iterator over `(&K, &mut V)` pairs, skipping empty slots.
+pub struct IterMut<'a, K, V> {
+    inner: std::slice::IterMut<'a, Option<Bucket<K, V>>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .by_ref()
+            .find_map(|slot| slot.as_mut().map(|b| (&b.key, &mut b.val)))
+    }
+}
+
+/// Owning iterator over `(K, V)` pairs, skipping empty slots.
+pub struct IntoIter<K, V> {
+    inner: std::vec::IntoIter<Option<Bucket<K, V>>>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.by_ref().find_map(|slot| slot.map(|b| (b.key, b.val)))
+    }
+}
+
+/// Iterator over the map's keys.
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, _)| k)
+    }
+}
+
+/// Iterator over the map's values.
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+}
+
+/// Iterator over mutable references to the map's values.
+pub struct ValuesMut<'a, K, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+}
+
+impl<K, V, S> IntoIterator for HashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.table.into_iter(),
         }
-        let hash = self.prehash(key);
-        let mut idx = (hash % self.table.len() as u64) as usize;
-        let mut cnt = 0;
-        while !matches!(self.table[idx], Entry::Empty) {
-            if let Entry::Pair {
-                key: ref ek,
-                val: ev,
-            } = &mut self.table[idx]
-            {
-                if ek.borrow() == key {
-                    // found and remove
-                    let mut tmp: V = Default::default();
-                    mem::swap(&mut tmp, ev);
-                    self.table[idx] = Entry::Del;
-                    self.items -= 1;
-                    self.tombs += 1;
-                    return Some(tmp);
-                }
-            }
-            // linear probing
-            idx = (idx + 1) % self.table.len();
-            if cnt > self.table.len() {
-                panic!("Infinite loop!")
-            }
-            cnt += 1;
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a HashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut HashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = HashMap::with_hasher(S::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, val) in iter {
+            self.insert(key, val);
         }
-        None
     }
 }
 
@@ -202,13 +634,24 @@ where
 mod tests {
     use super::*;
     #[test]
-    fn resize() {
+    fn reserve_grows_to_fit() {
         let mut map = HashMap::<&str, &str>::new();
         assert!(map.table.is_empty());
-        map.resize(false);
-        map.resize(false);
-        map.resize(false);
-        assert_eq!(map.table.len(), INITIAL_SIZE * 4)
+        map.reserve(100);
+        assert!(map.table.len().is_power_of_two());
+        assert!(capacity_for(map.table.len()) >= 100);
+        // Already large enough: reserving again must not shrink or realloc.
+        let buckets = map.table.len();
+        map.reserve(50);
+        assert_eq!(map.table.len(), buckets);
+    }
+    #[test]
+    fn try_reserve_overflow() {
+        let mut map = HashMap::<&str, &str>::new();
+        assert_eq!(
+            map.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
     }
     #[test]
     fn insert() {
@@ -232,26 +675,81 @@ mod tests {
         assert_eq!(map.get("foo"), None);
         map.insert("foo", 42);
         println!("[test]: {:?}", map.table);
-        assert_eq!(map.table.len(), 4)
+        assert_eq!(map.table.len(), MIN_BUCKETS)
     }
     #[test]
-    fn contaminate() {
+    fn churn() {
         let mut map = HashMap::new();
-        map.insert(12, 21); // size 1, items 1
-        map.insert(11, 11); // size 2, items 2
-        map.insert(99, 99); // size 4, items 3
-        map.remove(&12); // size 4, items 2, 1 tomb
-        map.insert(10, 10); // size 4, items 3, 1 tomb
-                            // size is still ok and not a time to growth
-                            // but table is contaminated and will panic
-                            // on insert, get(N/E), contains_key(N/E), remove(N/E)
+        map.insert(12, 21);
+        map.insert(11, 11);
+        map.insert(99, 99);
+        map.remove(&12);
+        map.insert(10, 10);
+        // backward-shift leaves no tombstone to confuse later lookups
         assert_eq!(map.get(&12), None);
+        assert_eq!(map.get(&10), Some(&10));
+        assert_eq!(map.get(&99), Some(&99));
+    }
+    #[test]
+    fn heavy_churn_stays_consistent() {
+        let mut map = HashMap::new();
+        for i in 0..2000 {
+            map.insert(i, i);
+        }
+        // Remove every other key, forcing many back-shifts.
+        for i in (0..2000).step_by(2) {
+            assert_eq!(map.remove(&i), Some(i));
+        }
+        assert_eq!(map.len(), 1000);
+        for i in 0..2000 {
+            if i % 2 == 0 {
+                assert_eq!(map.get(&i), None);
+            } else {
+                assert_eq!(map.get(&i), Some(&i));
+            }
+        }
+        // Re-insert removed keys; no tombstone build-up should block them.
+        for i in (0..2000).step_by(2) {
+            map.insert(i, i * 3);
+        }
+        assert_eq!(map.len(), 2000);
+        assert_eq!(map.get(&4), Some(&12));
     }
     #[test]
     fn empty_hashmap() {
         let mut map = HashMap::<&str, &str>::new();
-        assert_eq!(map.contains_key("key"), false);
+        assert!(!map.contains_key("key"));
         assert_eq!(map.get("key"), None);
         assert_eq!(map.remove("key"), None);
     }
+    #[test]
+    fn entry() {
+        let mut map = HashMap::new();
+        *map.entry("a").or_insert(0) += 1;
+        *map.entry("a").or_insert(0) += 1;
+        map.entry("b").or_insert_with(|| 10);
+        map.entry("a").and_modify(|v| *v *= 5).or_insert(0);
+        assert_eq!(map.get("a"), Some(&10));
+        assert_eq!(map.get("b"), Some(&10));
+        assert_eq!(map.entry("c").key(), &"c");
+        *map.entry("c").or_default() += 7;
+        assert_eq!(map.get("c"), Some(&7));
+    }
+    #[test]
+    fn iterate() {
+        let mut map: HashMap<&str, i32> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+        map.remove("b");
+        let mut keys: Vec<&str> = map.keys().copied().collect();
+        keys.sort_unstable();
+        assert_eq!(keys, vec!["a", "c"]);
+        let sum: i32 = map.values().sum();
+        assert_eq!(sum, 4);
+        for (_, v) in map.iter_mut() {
+            *v *= 10;
+        }
+        let total: i32 = (&map).into_iter().map(|(_, v)| *v).sum();
+        assert_eq!(total, 40);
+        let owned: Vec<(&str, i32)> = map.into_iter().collect();
+        assert_eq!(owned.len(), 2);
+    }
 }